@@ -10,6 +10,7 @@ use syn::{parse_macro_input, spanned::Spanned, DeriveInput, Error, Type};
 
 struct RawType {
     is_option: bool,
+    is_vec: bool,
     ident: syn::Ident,
 }
 
@@ -24,12 +25,26 @@ struct MyFieldReceiver {
     secret: Option<bool>,
 
     raw_type: Option<syn::Ident>,
+
+    /// Parses the field's value via `FromStr` instead of requiring a nested `ReplaceEnv` type.
+    /// Lets non-primitive types such as enums be read from a plain string, e.g. `${LOG_LEVEL:info}`.
+    from_str: Option<bool>,
+
+    /// Fallback used when the value string is a plain `${VAR}` token (no inline default) and the
+    /// environment variable is absent.
+    default: Option<String>,
+
+    /// When present, a missing environment variable is reported as an error instead of falling
+    /// back to any default.
+    required: Option<bool>,
 }
 
 struct TypeInfo {
     // Whether or not the ident is the type itself of was extracted from an Option<...>.
     is_option: bool,
-    // The actual type. Might have come from inside an Option<...>.
+    // Whether or not the ident was extracted from a Vec<...>.
+    is_vec: bool,
+    // The actual type. Might have come from inside an Option<...> or a Vec<...>.
     ident: Ident,
 }
 
@@ -45,6 +60,23 @@ fn get_type_info(ty: &Type) -> TypeInfo {
                             syn::GenericArgument::Type(t) => match t {
                                 Type::Path(p) => TypeInfo {
                                     is_option: true,
+                                    is_vec: false,
+                                    ident: p.path.segments[0].ident.clone(),
+                                },
+                                _ => abort!(span, "Only path types are supported!"),
+                            },
+                            _ => abort!(span, "Expected type in angle brackets!"),
+                        }
+                    }
+                    _ => abort!(span, "Expected angle brackets!"),
+                },
+                "Vec" => match &path.path.segments[0].arguments {
+                    syn::PathArguments::AngleBracketed(ab) => {
+                        match ab.args.first().expect("present") {
+                            syn::GenericArgument::Type(t) => match t {
+                                Type::Path(p) => TypeInfo {
+                                    is_option: false,
+                                    is_vec: true,
                                     ident: p.path.segments[0].ident.clone(),
                                 },
                                 _ => abort!(span, "Only path types are supported!"),
@@ -56,6 +88,7 @@ fn get_type_info(ty: &Type) -> TypeInfo {
                 },
                 _ => TypeInfo {
                     is_option: false,
+                    is_vec: false,
                     ident: path.path.segments[0].ident.clone(),
                 },
             }
@@ -66,26 +99,27 @@ fn get_type_info(ty: &Type) -> TypeInfo {
 
 impl MyFieldReceiver {
     pub fn raw_type(&self) -> Result<RawType, Error> {
-        let TypeInfo { is_option, ident } = get_type_info(&self.ty);
+        let TypeInfo { is_option, is_vec, ident } = get_type_info(&self.ty);
         self.raw_type.clone().map(|raw_type| {
             if raw_type == "String" {
                 match ident.to_string().as_str() {
                     "String" | "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" | "f32" | "f64" | "bool"
                     =>  abort! {self.ty.span(), "Do not specify `replace_env(raw_type = \"String\")` for primitive types for which 'String' will be the inferred type anyway."; help = "Remove attribute `replace_env(raw_type = \"String\")`"},
-                    _non_primitive_type => Ok(RawType { is_option, ident: raw_type })
+                    _non_primitive_type => Ok(RawType { is_option, is_vec, ident: raw_type })
                 }
             } else {
-                Ok(RawType { is_option, ident: raw_type })
+                Ok(RawType { is_option, is_vec, ident: raw_type })
             }
         }).unwrap_or_else(|| {
             match ident.to_string().as_str() {
                 "String" | "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" | "f32" | "f64" | "bool"
-                    => Ok(RawType { is_option, ident: Ident::new("String", self.ty.span()) }),
+                    => Ok(RawType { is_option, is_vec, ident: Ident::new("String", self.ty.span()) }),
+                _other if self.from_str.unwrap_or(false) => Ok(RawType { is_option, is_vec, ident: Ident::new("String", self.ty.span()) }),
                 other => {
                     let message = format!("Expected a primitive type like `String`, 'u32', ... But got: {other}");
                     abort!(
                         self.ty.span(), message;
-                        help = "Declare this field with: #[replace_env(raw_type = \"String\")] if it should be read as a string or #[replace_env(raw_type = \"YourOwnType\")] if its a type of your own that itself derived ReplaceEnv.";
+                        help = "Declare this field with: #[replace_env(raw_type = \"String\")] if it should be read as a string, #[replace_env(from_str)] if it implements `FromStr`, or #[replace_env(raw_type = \"YourOwnType\")] if its a type of your own that itself derived ReplaceEnv.";
                     );
                 }
             }
@@ -129,9 +163,16 @@ pub fn store(input: TokenStream) -> TokenStream {
         .map(|field| {
             let name = field.ident.as_ref().expect("Expected named field!");
             let secret = field.secret.unwrap_or(false);
+            let required = field.required.unwrap_or(false);
+            let default = match &field.default {
+                Some(default) => quote! { Some(#default.to_string()) },
+                None => quote! { None },
+            };
             quote! { #name: self.#name.replace_env(replace_env::Metadata {
                 secret: #secret,
-            }) }
+                default: #default,
+                required: #required,
+            })? }
         })
         .collect::<Vec<_>>();
 
@@ -161,80 +202,152 @@ pub fn store(input: TokenStream) -> TokenStream {
     let raw_field_type_declarations =
         fields_with_raw_type
             .iter()
-            .map(|(field, _type_info, RawType { is_option, ident })| {
+            .map(|(field, _type_info, RawType { is_option, is_vec, ident })| {
                 let name = field.ident.as_ref().expect("Expected named field!");
-                let raw_type = match is_option {
-                    true => quote! { Option<#ident> },
-                    false => quote! { #ident },
+                let raw_type = match (is_option, is_vec) {
+                    (true, _) => quote! { Option<#ident> },
+                    (false, true) => quote! { Vec<#ident> },
+                    (false, false) => quote! { #ident },
                 };
                 quote! {
                     #name: #raw_type
                 }
             });
 
-    let from_raw_field_initializers = fields_with_raw_type.iter().map(|(field, type_info, raw_type)| {
-        let name = field.ident.as_ref().expect("Expected named field!");
-        // Not every type can be transformed from its 'RawType' to its normal 'Type'.
-        // Special case: String -> bool: some_string.parse::<bool>();
+    let error_ident = Ident::new(format!("Raw{}Error", ident).as_str(), Span::call_site());
+
+    const PRIMITIVE_PARSEABLE_IDENTS: &[&str] = &[
+        "bool", "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128", "f32", "f64",
+    ];
+
+    // Whether a field's value is obtained by calling `str::parse` on it, either because its target
+    // type is a known primitive or because the user opted in via `#[replace_env(from_str)]`.
+    let uses_from_str = |field: &MyFieldReceiver, type_info: &TypeInfo| {
+        PRIMITIVE_PARSEABLE_IDENTS.contains(&type_info.ident.to_string().as_str()) || field.from_str.unwrap_or(false)
+    };
 
-        // TODO: Take raw type into consideration as well.
+    // Fields for which conversion may fail get a dedicated variant on the generated error enum.
+    let fallible_fields = fields_with_raw_type
+        .iter()
+        .filter(|(field, type_info, raw_type)| uses_from_str(field, type_info) || raw_type.ident != "String")
+        .collect::<Vec<_>>();
 
-        // If the user wants a boolean, we have to parse into a bool.
-        let conv_actiono = if type_info.ident == "bool" {
-            let expectation = format!("Expected field '{name}' to be of type bool. But value read was: '{{}}', which was not parsable to a bool. Use 'false' or 'true'. Original error was: '{{}}'");
+    let error_variants = fallible_fields.iter().map(|(field, type_info, raw_type)| {
+        let name = field.ident.as_ref().expect("Expected named field!");
+        let variant = Ident::new(to_pascal_case(&name.to_string()).as_str(), name.span());
+        if uses_from_str(field, type_info) {
+            let target_ident = &type_info.ident;
             quote! {
-                match orig.parse::<bool>() {
-                    Ok(val) => val,
-                    Err(err) => panic!(#expectation, orig, err) // TODO: Create error instead of panicking!
+                #variant {
+                    field: &'static str,
+                    value: String,
+                    source: <#target_ident as std::str::FromStr>::Err,
                 }
             }
-        }
-        // If the user wants a u32, we have to parse into a u32.
-        else if type_info.ident == "u32" {
-            let expectation = format!("Expected field '{name}' to be of type u32. But value read was: '{{}}', which was not parsable to a u32. Original error was: '{{}}'");
+        } else {
+            let raw_ident = &raw_type.ident;
+            let target_ident = &type_info.ident;
             quote! {
-                match orig.parse::<u32>() {
-                    Ok(val) => val,
-                    Err(err) => panic!(#expectation, orig, err) // TODO: Create error instead of panicking!
+                #variant {
+                    field: &'static str,
+                    source: <#target_ident as std::convert::TryFrom<#raw_ident>>::Error,
                 }
             }
-        // } else if path.path.segments[0].ident.to_string().starts_with("Raw") {
-        //     quote! { raw.#name.into() }
-        // } else if path.path.is_ident("String") {
-        //     quote! { raw.#name.into() }
+        }
+    });
+
+    let error_display_arms = fallible_fields.iter().map(|(field, type_info, _raw_type)| {
+        let name = field.ident.as_ref().expect("Expected named field!");
+        let variant = Ident::new(to_pascal_case(&name.to_string()).as_str(), name.span());
+        if uses_from_str(field, type_info) {
+            quote! {
+                #error_ident::#variant { field, value, source } => write!(f, "Field '{field}' could not be parsed from value '{value}': {source}")
+            }
+        } else {
+            quote! {
+                #error_ident::#variant { field, source } => write!(f, "Field '{field}' could not be converted: {source}")
+            }
+        }
+    });
+
+    let error_source_arms = fallible_fields.iter().map(|(field, _type_info, _raw_type)| {
+        let name = field.ident.as_ref().expect("Expected named field!");
+        let variant = Ident::new(to_pascal_case(&name.to_string()).as_str(), name.span());
+        quote! {
+            #error_ident::#variant { source, .. } => Some(source)
+        }
+    });
+
+    let try_from_raw_field_initializers = fields_with_raw_type.iter().map(|(field, type_info, raw_type)| {
+        let name = field.ident.as_ref().expect("Expected named field!");
+        let name_str = name.to_string();
+
+        // Not every type can be transformed from its 'RawType' to its normal 'Type'.
+        // Special case: String -> bool/numeric: some_string.parse::<bool>();
+
+        // If the user wants a bool, a numeric primitive, or opted into `#[replace_env(from_str)]`, we have to parse into that type.
+        let conv_action = if uses_from_str(field, type_info) {
+            let target_ident = &type_info.ident;
+            let variant = Ident::new(to_pascal_case(&name_str).as_str(), name.span());
+            quote! {
+                orig.parse::<#target_ident>().map_err(|source| #error_ident::#variant {
+                    field: #name_str,
+                    value: orig.clone(),
+                    source,
+                })?
+            }
+        } else if raw_type.ident != "String" {
+            let variant = Ident::new(to_pascal_case(&name_str).as_str(), name.span());
+            quote! {
+                std::convert::TryFrom::try_from(orig).map_err(|source| #error_ident::#variant {
+                    field: #name_str,
+                    source,
+                })?
+            }
         } else {
             quote! { orig.into() }
         };
 
-        let final_conv_action = match (raw_type.is_option, raw_type.ident == "String")  {
-            // TODO: Do we want empty check to be optional (based on user desire)? If excluded, parsing will typically fail...
-            (true, true) => quote! {
-                {
-                    let orig = raw.#name;
-                    match orig {
-                        Some(orig) => {
-                            if orig == "" {
-                                None
-                            } else {
-                                Some(#conv_actiono)
-                            }
-                        },
-                        None => None,
-                    }
-                }
-            },
-            (true, false) => quote! {
-                {
-                    let orig = raw.#name;
-                    orig.map(|orig| #conv_actiono)
-                }
-            },
-            (false, _) => quote! {
+        let final_conv_action = if raw_type.is_vec {
+            quote! {
                 {
                     let orig = raw.#name;
-                    #conv_actiono
+                    orig.into_iter()
+                        .map(|orig| -> Result<_, #error_ident> { Ok(#conv_action) })
+                        .collect::<Result<Vec<_>, _>>()?
                 }
-            },
+            }
+        } else {
+            match (raw_type.is_option, raw_type.ident == "String") {
+                // TODO: Do we want empty check to be optional (based on user desire)? If excluded, parsing will typically fail...
+                (true, true) => quote! {
+                    {
+                        let orig = raw.#name;
+                        match orig {
+                            Some(orig) => {
+                                if orig == "" {
+                                    None
+                                } else {
+                                    Some(#conv_action)
+                                }
+                            },
+                            None => None,
+                        }
+                    }
+                },
+                (true, false) => quote! {
+                    {
+                        let orig = raw.#name;
+                        orig.map(|orig| -> Result<_, #error_ident> { Ok(#conv_action) }).transpose()?
+                    }
+                },
+                (false, _) => quote! {
+                    {
+                        let orig = raw.#name;
+                        #conv_action
+                    }
+                },
+            }
         };
 
         quote! { #name: #final_conv_action }
@@ -242,9 +355,10 @@ pub fn store(input: TokenStream) -> TokenStream {
 
     // This is our derive implementation. We create:
     // 1. The RawType (in which all fields are Strings or special user-defined raw types) using the `raw_type_ident` and the `raw_field_type_declarations`.
-    // 2. The From<'raw_type_ident'> for 'ident' conversion, with which a raw type instance can be converted in its real representation.
-    //    This converts all string/raw fields to their real type, parsing booleans, integers, floats or converting strings to enum value using serde.
-    // 3. The ReplaceEnv implementation for the 'raw_type_ident' which lets us replace environment variable names in the raw types fields before converting it to our real type.
+    // 2. The error enum carrying one variant per fallibly-converted field, plus its Display/Error impls.
+    // 3. The TryFrom<'raw_type_ident'> for 'ident' conversion, with which a raw type instance can be converted into its real representation.
+    //    This converts all string/raw fields to their real type, parsing booleans, integers or floats, returning an error instead of panicking on failure.
+    // 4. The ReplaceEnv implementation for the 'raw_type_ident' which lets us replace environment variable names in the raw types fields before converting it to our real type.
     quote! {
         // 1.
         #[derive(Debug, serde::Deserialize)]
@@ -253,22 +367,61 @@ pub fn store(input: TokenStream) -> TokenStream {
         }
 
         // 2.
-        impl From<#raw_type_ident> for #ident {
-            fn from(raw: #raw_type_ident) -> Self {
-                Self {
-                    #(#from_raw_field_initializers),*
+        #[derive(Debug)]
+        pub enum #error_ident {
+            #(#error_variants),*
+        }
+
+        impl std::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    #(#error_display_arms),*
+                }
+            }
+        }
+
+        impl std::error::Error for #error_ident {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                match self {
+                    #(#error_source_arms),*
                 }
             }
         }
 
         // 3.
+        impl std::convert::TryFrom<#raw_type_ident> for #ident {
+            type Error = #error_ident;
+
+            fn try_from(raw: #raw_type_ident) -> Result<Self, Self::Error> {
+                Ok(Self {
+                    #(#try_from_raw_field_initializers),*
+                })
+            }
+        }
+
+        // 4.
         impl replace_env::ReplaceEnv for #raw_type_ident {
-            fn replace_env(self, _metadata: replace_env::Metadata) -> Self {
-                Self {
+            fn replace_env(self, _metadata: replace_env::Metadata) -> Result<Self, replace_env::ReplaceEnvError> {
+                Ok(Self {
                     #(#replace_env_field_initializers),*
-                }
+                })
             }
         }
     }
     .into()
 }
+
+/// Converts a `snake_case` identifier into `PascalCase`, for use as an error enum variant name.
+fn to_pascal_case(snake_case: &str) -> String {
+    snake_case
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -3,58 +3,137 @@
 
 pub use derive_replace_env::ReplaceEnv;
 
+#[derive(Clone)]
 pub struct Metadata {
     pub secret: bool,
+
+    /// Used when the value string is a plain `${VAR}` token (no inline default) and the
+    /// environment variable is absent. Set via `#[replace_env(default = "...")]`.
+    pub default: Option<String>,
+
+    /// When `true`, a missing environment variable is reported as an error instead of falling
+    /// back to any default. Set via `#[replace_env(required)]`.
+    pub required: bool,
+}
+
+/// Error produced while resolving environment variables into a value string.
+#[derive(Debug)]
+pub enum ReplaceEnvError {
+    /// A field was marked `#[replace_env(required)]`, but its environment variable was not set
+    /// or did not contain valid unicode.
+    MissingRequiredVar { var: String },
 }
 
+impl std::fmt::Display for ReplaceEnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplaceEnvError::MissingRequiredVar { var } => {
+                write!(f, "ENV variable \"{var}\" is required, but was not present or did not contain valid unicode.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplaceEnvError {}
+
 pub trait ReplaceEnv {
-    fn replace_env(self, metadata: Metadata) -> Self;
+    fn replace_env(self, metadata: Metadata) -> Result<Self, ReplaceEnvError>
+    where
+        Self: Sized;
 }
 
 impl ReplaceEnv for String {
-    fn replace_env(mut self, metadata: Metadata) -> Self {
-        replace_env_in_string(&mut self, metadata);
-        self
+    fn replace_env(mut self, metadata: Metadata) -> Result<Self, ReplaceEnvError> {
+        replace_env_in_string(&mut self, metadata)?;
+        Ok(self)
     }
 }
 
 impl<T: ReplaceEnv> ReplaceEnv for Option<T> {
-    fn replace_env(self, metadata: Metadata) -> Self {
-        self.map(|it| it.replace_env(metadata))
+    fn replace_env(self, metadata: Metadata) -> Result<Self, ReplaceEnvError> {
+        self.map(|it| it.replace_env(metadata)).transpose()
+    }
+}
+
+impl<T: ReplaceEnv> ReplaceEnv for Vec<T> {
+    fn replace_env(self, metadata: Metadata) -> Result<Self, ReplaceEnvError> {
+        self.into_iter().map(|it| it.replace_env(metadata.clone())).collect()
     }
 }
 
-/// Checks if the given string starts with "${", ends with "}" and contains at least one ":". Only then modifies the given `string` by
-/// trying to obtain the value of the environment variable denoted by the substring after "${" and before the first ":".
-/// If that value could be determined, replaces the whole string with that value.
-/// If that value could not be determined, replaces the whole string with the default value,
-/// denoted by the substring starting after the first ":" end ending before "}".
-fn replace_env_in_string(string: &mut String, metadata: Metadata) {
-    if string.starts_with(['$', '{']) && string.ends_with('}') {
-        if let Some((env_name, default_value)) = string.split_once(':') {
-            let env_name = &env_name[2..env_name.len()]; // Remove leading "${".
-            let default_value = &default_value[0..default_value.len() - 1]; // Remove trailing "}".
-            match std::env::var(env_name) {
-                Ok(env_value) => {
-                    string.clear();
-                    string.push_str(env_value.as_str());
-                }
-                Err(var_error) => {
-                    match var_error {
-                        std::env::VarError::NotPresent => match metadata.secret {
-                            false => tracing::warn!("ENV variable \"{env_name}\" not present. Using default: \"{default_value}\""),
-                            true => tracing::warn!("ENV variable \"{env_name}\" not present. Using secret default."),
-                        },
-                        std::env::VarError::NotUnicode(_) => match metadata.secret {
-                            false => tracing::warn!("ENV variable \"{env_name}\" doest not contain valid unicode! Using default: \"{default_value}\""),
-                            true => tracing::warn!("ENV variable \"{env_name}\" doest not contain valid unicode! Using secret default."),
-                        },
-                    }
-                    let default_string = default_value.to_string();
-                    string.clear();
-                    string.push_str(default_string.as_str());
-                }
+/// Scans `string` left-to-right for `${NAME}` / `${NAME:default}` tokens embedded anywhere in the
+/// surrounding literal text (e.g. `postgres://${PGUSER:admin}:${PGPASS:}@${PGHOST:localhost}:5432/db`)
+/// and substitutes each one in place with the resolved environment variable value.
+///
+/// For each token, the substring up to the first ":" is the variable name; everything after that and
+/// before the closing "}" is its inline default (a default may itself contain colons, e.g. a URL).
+/// A token without a ":" has no inline default.
+///
+/// If a variable could not be determined:
+/// - returns an error if `metadata.required` is set,
+/// - otherwise substitutes the inline default, falling back to `metadata.default` if no inline default
+///   was given, and to an empty string if neither is present.
+///
+/// Literal text, stray "$" characters and unterminated "${" sequences are left untouched.
+fn replace_env_in_string(string: &mut String, metadata: Metadata) -> Result<(), ReplaceEnvError> {
+    let original = std::mem::take(string);
+    let mut result = String::with_capacity(original.len());
+    let mut rest = original.as_str();
+
+    while let Some(token_start) = rest.find("${") {
+        result.push_str(&rest[..token_start]);
+        let after_token_start = &rest[token_start + 2..];
+
+        let Some(token_end) = after_token_start.find('}') else {
+            // Unterminated "${" - leave the remainder of the string untouched.
+            result.push_str(&rest[token_start..]);
+            rest = "";
+            break;
+        };
+
+        let token = &after_token_start[..token_end];
+        let (env_name, inline_default) = match token.split_once(':') {
+            Some((env_name, inline_default)) => (env_name, Some(inline_default)),
+            None => (token, None),
+        };
+
+        result.push_str(&resolve_env_var(env_name, inline_default, &metadata)?);
+        rest = &after_token_start[token_end + 1..];
+    }
+    result.push_str(rest);
+
+    *string = result;
+    Ok(())
+}
+
+/// Resolves a single `${env_name:inline_default}` token to its replacement value.
+fn resolve_env_var(env_name: &str, inline_default: Option<&str>, metadata: &Metadata) -> Result<String, ReplaceEnvError> {
+    match std::env::var(env_name) {
+        Ok(env_value) => Ok(env_value),
+        Err(var_error) => {
+            if metadata.required {
+                return Err(ReplaceEnvError::MissingRequiredVar {
+                    var: env_name.to_string(),
+                });
+            }
+
+            let default_value = inline_default
+                .map(str::to_string)
+                .or_else(|| metadata.default.clone())
+                .unwrap_or_default();
+
+            match var_error {
+                std::env::VarError::NotPresent => match metadata.secret {
+                    false => tracing::warn!("ENV variable \"{env_name}\" not present. Using default: \"{default_value}\""),
+                    true => tracing::warn!("ENV variable \"{env_name}\" not present. Using secret default."),
+                },
+                std::env::VarError::NotUnicode(_) => match metadata.secret {
+                    false => tracing::warn!("ENV variable \"{env_name}\" doest not contain valid unicode! Using default: \"{default_value}\""),
+                    true => tracing::warn!("ENV variable \"{env_name}\" doest not contain valid unicode! Using secret default."),
+                },
             }
+
+            Ok(default_value)
         }
     }
 }